@@ -5,22 +5,285 @@ use std::{str::FromStr, sync::OnceLock};
 use eyre::{eyre, Context as _, Result};
 use log::{debug, trace};
 use poise::serenity_prelude::{
-	Cache, CacheHttp, ChannelId, ChannelType, Colour, Context, CreateEmbed, CreateEmbedAuthor,
-	CreateEmbedFooter, GuildChannel, Member, Message, MessageId, Permissions, UserId,
+	Attachment, Cache, CacheHttp, ChannelId, ChannelType, Colour, Context, CreateAttachment,
+	CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter, CreateWebhook, ExecuteWebhook, GuildChannel,
+	GuildId, Member, Message, MessageId, Permissions, UserId, Webhook,
 };
 use regex::Regex;
 
-fn find_first_image(message: &Message) -> Option<String> {
-	message
-		.attachments
-		.iter()
-		.find(|a| {
-			a.content_type
-				.as_ref()
-				.unwrap_or(&String::new())
-				.starts_with("image/")
-		})
-		.map(|res| res.url.clone())
+/// Limits applied when re-hosting a quoted message's attachments. Discord CDN
+/// URLs now expire, so re-uploading the bytes keeps expanded quotes viewable
+/// indefinitely; these bounds stop us proxying arbitrarily large files.
+pub struct AttachmentConfig {
+	/// Largest single attachment we will download and re-upload, in bytes.
+	pub max_size: u64,
+	/// Most attachments we will re-upload for a single message.
+	pub max_count: usize,
+}
+
+impl Default for AttachmentConfig {
+	fn default() -> Self {
+		Self {
+			max_size: 8 * 1024 * 1024,
+			max_count: 10,
+		}
+	}
+}
+
+/// When, if ever, a link may be expanded into a guild other than the one it was
+/// posted in. Cross-guild resolution is opt-in because it can surface content a
+/// reader couldn't otherwise see; whichever policy is chosen, the requester's
+/// own membership and permissions in the target guild are always re-checked.
+#[derive(Default)]
+pub enum CrossGuildPolicy {
+	/// Never expand links pointing at another guild (the historical behavior).
+	#[default]
+	Disabled,
+	/// Expand links into any guild the bot shares with the requester.
+	SharedMembership,
+	/// Expand only between explicitly allowlisted (unordered) guild pairs.
+	Allowlist(Vec<(GuildId, GuildId)>),
+}
+
+impl CrossGuildPolicy {
+	/// Whether expanding a link posted in `from` that points at `to` is allowed
+	/// by policy alone — membership and channel permissions are verified
+	/// separately by the caller.
+	fn allows(&self, from: GuildId, to: GuildId) -> bool {
+		match self {
+			CrossGuildPolicy::Disabled => false,
+			CrossGuildPolicy::SharedMembership => true,
+			CrossGuildPolicy::Allowlist(pairs) => pairs
+				.iter()
+				.any(|&(a, b)| (a == from && b == to) || (a == to && b == from)),
+		}
+	}
+}
+
+/// Most images we fold into a single gallery. Discord groups up to four embeds
+/// that share one `url` field into one image gallery.
+const MAX_GALLERY_IMAGES: usize = 4;
+
+fn is_image(attachment: &Attachment) -> bool {
+	attachment
+		.content_type
+		.as_ref()
+		.is_some_and(|kind| kind.starts_with("image/"))
+}
+
+/// Source for a gallery image: the re-uploaded copy when we have one, otherwise
+/// the original CDN URL.
+fn gallery_image_src(attachment: &Attachment, uploads: &[CreateAttachment]) -> String {
+	if uploads.iter().any(|u| u.filename == attachment.filename) {
+		format!("attachment://{}", attachment.filename)
+	} else {
+		attachment.url.clone()
+	}
+}
+
+/// Download an attachment through the shared [`HttpClient`] and wrap it as a
+/// [`CreateAttachment`] for re-upload. Returns `None` — so the caller falls back
+/// to the CDN link — when the attachment is over `max_size` or the fetch fails.
+async fn reupload_attachment(
+	http: &HttpClient,
+	attachment: &Attachment,
+	max_size: u64,
+) -> Option<CreateAttachment> {
+	if u64::from(attachment.size) > max_size {
+		debug!(
+			"Attachment {} exceeds re-upload size limit, keeping link",
+			attachment.filename
+		);
+		return None;
+	}
+
+	match http.get(&attachment.url).send().await {
+		Ok(response) if !response.status().is_success() => {
+			debug!(
+				"Couldn't download attachment {}: HTTP {}",
+				attachment.filename,
+				response.status()
+			);
+			None
+		}
+		Ok(response) => match response.bytes().await {
+			Ok(bytes) => Some(CreateAttachment::bytes(
+				bytes.to_vec(),
+				attachment.filename.clone(),
+			)),
+			Err(err) => {
+				debug!("Couldn't read attachment {}: {err}", attachment.filename);
+				None
+			}
+		},
+		Err(err) => {
+			debug!("Couldn't download attachment {}: {err}", attachment.filename);
+			None
+		}
+	}
+}
+
+/// Longest reply quote we inline before truncating with an ellipsis.
+const REPLY_QUOTE_MAX_LEN: usize = 100;
+
+/// Resolve the message a reply points at, preferring the `referenced_message`
+/// Discord already inlined and falling back to a fetch via `message_reference`.
+async fn referenced_parent(
+	ctx: impl CacheHttp + AsRef<Cache>,
+	message: &Message,
+) -> Result<Option<Message>> {
+	if let Some(parent) = &message.referenced_message {
+		return Ok(Some((**parent).clone()));
+	}
+
+	let Some(reference) = &message.message_reference else {
+		return Ok(None);
+	};
+	let Some(parent_id) = reference.message_id else {
+		return Ok(None);
+	};
+
+	let parent = reference
+		.channel_id
+		.message(&ctx, parent_id)
+		.await
+		.wrap_err_with(|| eyre!("Couldn't fetch referenced message {parent_id}!"))?;
+	Ok(Some(parent))
+}
+
+/// Build a compact "Reply to:" embed quoting the parent of a replied-to
+/// message, so readers get the conversational thread instead of an orphaned
+/// quote. Returns `None` when the message isn't a reply.
+async fn reply_embed(
+	ctx: impl CacheHttp + AsRef<Cache>,
+	message: &Message,
+) -> Result<Option<CreateEmbed>> {
+	let Some(parent) = referenced_parent(&ctx, message).await? else {
+		return Ok(None);
+	};
+
+	let nick = parent
+		.author_nick(&ctx)
+		.await
+		.unwrap_or_else(|| parent.author.name.clone());
+
+	let author = CreateEmbedAuthor::new(format!("{nick} ↩️")).icon_url(
+		parent
+			.author
+			.avatar_url()
+			.unwrap_or_else(|| parent.author.default_avatar_url()),
+	);
+
+	let quote = if parent.content.chars().count() > REPLY_QUOTE_MAX_LEN {
+		let head: String = parent.content.chars().take(REPLY_QUOTE_MAX_LEN).collect();
+		format!("{head}…")
+	} else {
+		parent.content.clone()
+	};
+
+	let embed = CreateEmbed::new()
+		.author(author)
+		.color(Colour::BLITZ_BLUE)
+		.description(format!("**[Reply to:]({})**\n{}", parent.link(), quote));
+
+	Ok(Some(embed))
+}
+
+/// Name given to the webhook we manage in each channel for author-faithful reposts.
+const WEBHOOK_NAME: &str = "Prism";
+
+/// Find the channel's existing webhook named "Prism" or create one. Existing
+/// webhooks are matched by name only, whoever created them.
+async fn get_or_create_webhook_for_channel(
+	ctx: impl CacheHttp,
+	channel_id: ChannelId,
+) -> Result<Webhook> {
+	let existing = channel_id.webhooks(ctx.http()).await?;
+	if let Some(webhook) = existing
+		.into_iter()
+		.find(|w| w.name.as_deref() == Some(WEBHOOK_NAME))
+	{
+		return Ok(webhook);
+	}
+
+	channel_id
+		.create_webhook(ctx.http(), CreateWebhook::new(WEBHOOK_NAME))
+		.await
+		.wrap_err_with(|| eyre!("Couldn't create webhook in channel {channel_id}!"))
+}
+
+/// Repost a resolved message through a per-channel webhook so it visibly appears
+/// as the original author (display name + avatar) rather than a bot embed. This
+/// is the more natural alternative to [`to_embed`] for servers that prefer it,
+/// and reuses [`find_real_author_id`] so PluralKit-proxied senders keep their
+/// human identity.
+pub async fn repost_via_webhook(
+	ctx: &Context,
+	http: &HttpClient,
+	channel_id: ChannelId,
+	message: &Message,
+	config: &AttachmentConfig,
+) -> Result<()> {
+	let webhook = get_or_create_webhook_for_channel(ctx, channel_id).await?;
+
+	let author_id = if message.webhook_id.is_some() {
+		find_real_author_id(http, message).await
+	} else {
+		message.author.id
+	};
+
+	let (username, avatar_url) = if let Some(guild_id) = message.guild_id {
+		let member = guild_id.member(ctx, author_id).await?;
+		let name = member.display_name().to_owned();
+		let avatar = member
+			.avatar_url()
+			.or_else(|| member.user.avatar_url())
+			.unwrap_or_else(|| member.user.default_avatar_url());
+		(name, avatar)
+	} else {
+		(
+			message.author.display_name().to_owned(),
+			message
+				.author
+				.avatar_url()
+				.unwrap_or_else(|| message.author.default_avatar_url()),
+		)
+	};
+
+	let mut builder = ExecuteWebhook::new()
+		.username(username)
+		.avatar_url(avatar_url);
+
+	// Attachment/image-only messages carry empty content, and a webhook with no
+	// content, embeds, or files is a "Cannot send an empty message" 400 — so
+	// re-upload the attachments to carry the message instead, honoring the same
+	// size/count limits as the embed path.
+	let mut files = Vec::new();
+	for attachment in &message.attachments {
+		if files.len() >= config.max_count {
+			break;
+		}
+		if let Some(file) = reupload_attachment(http, attachment, config.max_size).await {
+			files.push(file);
+		}
+	}
+
+	if message.content.trim().is_empty() && files.is_empty() {
+		debug!("Skipping webhook repost of empty message {}", message.id);
+		return Ok(());
+	}
+
+	if !message.content.trim().is_empty() {
+		builder = builder.content(&message.content);
+	}
+	builder = builder.files(files);
+
+	webhook
+		.execute(ctx.http(), false, builder)
+		.await
+		.wrap_err_with(|| eyre!("Couldn't execute webhook in channel {channel_id}!"))?;
+
+	Ok(())
 }
 
 async fn find_real_author_id(http: &HttpClient, message: &Message) -> UserId {
@@ -67,8 +330,10 @@ async fn member_can_view_channel(
 
 pub async fn to_embed(
 	ctx: impl CacheHttp + AsRef<Cache>,
+	http: &HttpClient,
 	message: &Message,
-) -> Result<CreateEmbed> {
+	config: &AttachmentConfig,
+) -> Result<(Vec<CreateEmbed>, Vec<CreateAttachment>)> {
 	let author = CreateEmbedAuthor::new(message.author.tag()).icon_url(
 		message
 			.author
@@ -81,45 +346,344 @@ pub async fn to_embed(
 		message.channel(ctx).await?.guild().unwrap_or_default().name
 	));
 
+	let link = message.link();
+
+	// first few images become a gallery; the rest fall through to link fields
+	let gallery: Vec<&Attachment> = message
+		.attachments
+		.iter()
+		.filter(|a| is_image(a))
+		.take(MAX_GALLERY_IMAGES)
+		.collect();
+
+	// Re-host as many attachments as the limits allow; the rest keep their
+	// (eventually-expiring) CDN link, as an "Attachments" field or, for gallery
+	// images, as the embed image.
+	let mut uploads: Vec<CreateAttachment> = Vec::new();
+	let mut link_fields: Vec<(String, String, bool)> = Vec::new();
+
+	for attachment in &message.attachments {
+		let reuploaded = if uploads.len() < config.max_count {
+			reupload_attachment(http, attachment, config.max_size).await
+		} else {
+			None
+		};
+
+		let is_gallery_image = gallery.iter().any(|g| g.id == attachment.id);
+
+		match reuploaded {
+			// gallery images are rendered inline via the embed image; everything
+			// else re-uploaded still needs a link so readers can reach it — now
+			// pointing at the re-hosted copy rather than the expiring CDN URL.
+			Some(file) => {
+				if !is_gallery_image {
+					link_fields.push((
+						"Attachments".to_string(),
+						format!("[{}](attachment://{})", attachment.filename, file.filename),
+						false,
+					));
+				}
+				uploads.push(file);
+			}
+			// gallery images are rendered inline, not listed as links
+			None if is_gallery_image => {}
+			None => link_fields.push((
+				"Attachments".to_string(),
+				format!("[{}]({})", attachment.filename, attachment.url),
+				false,
+			)),
+		}
+	}
+
 	let mut embed = CreateEmbed::new()
 		.author(author)
 		.color(Colour::BLITZ_BLUE)
 		.timestamp(message.timestamp)
 		.footer(footer)
-		.description(format!(
-			"{}\n\n[Jump to original message]({})",
-			message.content,
-			message.link()
-		));
-
-	if !message.attachments.is_empty() {
-		embed = embed.fields(message.attachments.iter().map(|a| {
-			(
-				"Attachments".to_string(),
-				format!("[{}]({})", a.filename, a.url),
-				false,
-			)
-		}));
+		.url(&link)
+		.description(format!("{}\n\n[Jump to original message]({})", message.content, link));
+
+	if !link_fields.is_empty() {
+		embed = embed.fields(link_fields);
+	}
+
+	// Every gallery embed shares the same `url`, so the client collapses them
+	// into one gallery under this first embed's author/footer/description.
+	let mut gallery = gallery.into_iter();
+	if let Some(first) = gallery.next() {
+		embed = embed.image(gallery_image_src(first, &uploads));
+	}
+
+	let mut embeds = vec![embed];
+	for image in gallery {
+		embeds.push(
+			CreateEmbed::new()
+				.url(&link)
+				.image(gallery_image_src(image, &uploads)),
+		);
+	}
+
+	Ok((embeds, uploads))
+}
+
+/// The embeds (and any re-uploaded attachments) produced by resolving a single
+/// recognized link. Every per-type handler returns one of these, so
+/// [`from_message`] just concatenates across all matched references.
+#[derive(Default)]
+struct ResolvedLink {
+	embeds: Vec<CreateEmbed>,
+	attachments: Vec<CreateAttachment>,
+}
+
+/// A Discord reference we know how to expand, extracted from message content.
+/// New shapes (events, roles, …) become new variants plus a handler, rather
+/// than another branch grafted onto a single regex.
+enum LinkTarget {
+	Message {
+		guild_id: GuildId,
+		channel_id: ChannelId,
+		message_id: MessageId,
+	},
+	Channel {
+		guild_id: GuildId,
+		channel_id: ChannelId,
+	},
+	User {
+		user_id: UserId,
+	},
+}
+
+/// Scan `content` for every reference shape we recognize. Message and channel
+/// links share one pattern — a `channels/<guild>/<channel>` URL with an
+/// optional trailing `/<message>` — so they can never double-match, which the
+/// regex crate's lack of look-around would otherwise make awkward.
+fn parse_targets(content: &str) -> Vec<LinkTarget> {
+	static LINK_PATTERN: OnceLock<Regex> = OnceLock::new();
+	let link_pattern = LINK_PATTERN.get_or_init(|| Regex::new(r"(?:https?:\/\/)?(?:canary\.|ptb\.)?discord(?:app)?\.com\/channels\/(?<guild_id>\d+)\/(?<channel_id>\d+)(?:\/(?<message_id>\d+))?").unwrap());
+
+	static USER_PATTERN: OnceLock<Regex> = OnceLock::new();
+	let user_pattern =
+		USER_PATTERN.get_or_init(|| Regex::new(r"<@!?(?<user_id>\d+)>").unwrap());
+
+	let mut targets = Vec::new();
+
+	for caps in link_pattern.captures_iter(content) {
+		let (Ok(guild_id), Ok(channel_id)) = (
+			GuildId::from_str(&caps["guild_id"]),
+			ChannelId::from_str(&caps["channel_id"]),
+		) else {
+			continue;
+		};
+
+		match caps
+			.name("message_id")
+			.and_then(|m| MessageId::from_str(m.as_str()).ok())
+		{
+			Some(message_id) => targets.push(LinkTarget::Message {
+				guild_id,
+				channel_id,
+				message_id,
+			}),
+			None => targets.push(LinkTarget::Channel {
+				guild_id,
+				channel_id,
+			}),
+		}
+	}
+
+	// Only expand a user reference when it's the *entire* message — a bare,
+	// standalone mention the author clearly meant as a lookup. Mentions embedded
+	// in ordinary conversation (pinging someone mid-sentence) are left alone, so
+	// we don't spam unsolicited user-info embeds.
+	if let Some(caps) = user_pattern.captures(content.trim()) {
+		if caps.get(0).is_some_and(|m| m.as_str() == content.trim()) {
+			if let Ok(user_id) = UserId::from_str(&caps["user_id"]) {
+				targets.push(LinkTarget::User { user_id });
+			}
+		}
+	}
+
+	targets
+}
+
+/// Resolve the target channel behind a link only if the requesting `author` may
+/// actually see it. For same-guild links the requester is already resolved; for
+/// cross-guild ones they're re-resolved as a member of the *target* guild (and
+/// we bail silently if they aren't one) before the channel's own
+/// `VIEW_CHANNEL | READ_MESSAGE_HISTORY` check, so nobody can leak content from
+/// a server — or a private channel — they can't actually see. Returns `None`
+/// whenever any gate fails.
+async fn viewable_target_channel(
+	ctx: &Context,
+	author: &Member,
+	guild_id: GuildId,
+	target_guild_id: GuildId,
+	target_channel_id: ChannelId,
+	policy: &CrossGuildPolicy,
+) -> Result<Option<GuildChannel>> {
+	let target_member = if target_guild_id == guild_id {
+		author.clone()
+	} else {
+		if !policy.allows(guild_id, target_guild_id) {
+			debug!("Not resolving link from other server");
+			return Ok(None);
+		}
+
+		match target_guild_id.member(ctx, author.user.id).await {
+			Ok(member) => member,
+			Err(_) => {
+				debug!("Not resolving cross-guild link for non-member");
+				return Ok(None);
+			}
+		}
+	};
+
+	let target_channel = target_channel_id
+		.to_channel(ctx)
+		.await?
+		.guild()
+		.ok_or_else(|| eyre!("Couldn't find GuildChannel from ChannelId {target_channel_id}!"))?;
+
+	// Channel IDs come from attacker-controlled URL text, so a link can name a
+	// guild the requester belongs to while pointing at a channel in a different
+	// one. The membership we just checked only means something if it matches the
+	// guild the channel actually lives in — otherwise the permission check would
+	// weigh the requester's (foreign) roles against the real guild.
+	if target_channel.guild_id != target_guild_id {
+		debug!("Not resolving link whose channel isn't in the claimed guild");
+		return Ok(None);
+	}
+
+	if !member_can_view_channel(ctx, &target_member, &target_channel).await? {
+		debug!("Not resolving link for author who can't see it");
+		return Ok(None);
+	}
+
+	Ok(Some(target_channel))
+}
+
+/// Expand a message link into a reply-context embed (if any) plus the quote
+/// gallery, after checking the requesting `author` may actually see it.
+#[allow(clippy::too_many_arguments)]
+async fn resolve_message_link(
+	ctx: &Context,
+	http: &HttpClient,
+	author: &Member,
+	guild_id: GuildId,
+	target_guild_id: GuildId,
+	target_channel_id: ChannelId,
+	target_message_id: MessageId,
+	config: &AttachmentConfig,
+	policy: &CrossGuildPolicy,
+) -> Result<ResolvedLink> {
+	let Some(target_channel) =
+		viewable_target_channel(ctx, author, guild_id, target_guild_id, target_channel_id, policy)
+			.await?
+	else {
+		return Ok(ResolvedLink::default());
+	};
+
+	trace!("Attempting to resolve message {target_message_id}");
+
+	let target_message = target_channel
+		.message(ctx, target_message_id)
+		.await
+		.wrap_err_with(|| eyre!("Couldn't find channel message from ID {target_message_id}!"))?;
+
+	let mut embeds = Vec::new();
+	if let Some(reply) = reply_embed(ctx, &target_message).await? {
+		embeds.push(reply);
+	}
+
+	let (mut gallery, attachments) = to_embed(ctx, http, &target_message, config).await?;
+	embeds.append(&mut gallery);
+
+	Ok(ResolvedLink {
+		embeds,
+		attachments,
+	})
+}
+
+/// Expand a channel link into a small channel-info embed, after confirming the
+/// requesting `author` may actually see the target channel — the same
+/// cross-guild and per-channel permission gate applied to message links, so a
+/// pasted link to a private (or other-server) channel never leaks its name and
+/// topic.
+async fn resolve_channel_link(
+	ctx: &Context,
+	author: &Member,
+	guild_id: GuildId,
+	target_guild_id: GuildId,
+	channel_id: ChannelId,
+	policy: &CrossGuildPolicy,
+) -> Result<ResolvedLink> {
+	let Some(channel) =
+		viewable_target_channel(ctx, author, guild_id, target_guild_id, channel_id, policy).await?
+	else {
+		return Ok(ResolvedLink::default());
+	};
+
+	let mut description = format!("<#{}>", channel.id);
+	if let Some(topic) = channel.topic.as_deref().filter(|t| !t.is_empty()) {
+		description.push_str("\n\n");
+		description.push_str(topic);
+	}
 
-		if let Some(image) = find_first_image(message) {
-			embed = embed.image(image);
+	let embed = CreateEmbed::new()
+		.author(CreateEmbedAuthor::new(format!("#{}", channel.name)))
+		.color(Colour::BLITZ_BLUE)
+		.footer(CreateEmbedFooter::new(
+			channel.guild_id.name(ctx).unwrap_or_default(),
+		))
+		.description(description);
+
+	Ok(ResolvedLink {
+		embeds: vec![embed],
+		attachments: Vec::new(),
+	})
+}
+
+/// Expand a user mention/ID into a small user-info embed, enriched with guild
+/// membership when the user is a member of the requesting guild.
+async fn resolve_user_link(
+	ctx: &Context,
+	guild_id: GuildId,
+	user_id: UserId,
+) -> Result<ResolvedLink> {
+	let user = user_id.to_user(ctx).await?;
+	let avatar = user
+		.avatar_url()
+		.unwrap_or_else(|| user.default_avatar_url());
+
+	let mut embed = CreateEmbed::new()
+		.author(CreateEmbedAuthor::new(user.tag()).icon_url(&avatar))
+		.color(Colour::BLITZ_BLUE)
+		.thumbnail(avatar)
+		.timestamp(user.id.created_at())
+		.description(format!("<@{}>", user.id));
+
+	if let Ok(member) = guild_id.member(ctx, user_id).await {
+		if let Some(joined) = member.joined_at {
+			embed = embed.field("Joined", format!("<t:{}:R>", joined.unix_timestamp()), true);
 		}
 	}
 
-	Ok(embed)
+	Ok(ResolvedLink {
+		embeds: vec![embed],
+		attachments: Vec::new(),
+	})
 }
 
 pub async fn from_message(
 	ctx: &Context,
 	http: &HttpClient,
 	msg: &Message,
-) -> Result<Vec<CreateEmbed>> {
-	static MESSAGE_PATTERN: OnceLock<Regex> = OnceLock::new();
-	let message_pattern = MESSAGE_PATTERN.get_or_init(|| Regex::new(r"(?:https?:\/\/)?(?:canary\.|ptb\.)?discord(?:app)?\.com\/channels\/(?<server_id>\d+)\/(?<channel_id>\d+)\/(?<message_id>\d+)").unwrap());
-
+	config: &AttachmentConfig,
+	policy: &CrossGuildPolicy,
+) -> Result<(Vec<CreateEmbed>, Vec<CreateAttachment>)> {
 	let Some(guild_id) = msg.guild_id else {
 		debug!("Not resolving message in DM");
-		return Ok(Vec::new());
+		return Ok((Vec::new(), Vec::new()));
 	};
 
 	// if the message was sent through pluralkit, we'll want
@@ -132,44 +696,49 @@ pub async fn from_message(
 
 	let author = guild_id.member(ctx, author_id).await?;
 
-	let matches = message_pattern
-		.captures_iter(&msg.content)
-		.map(|capture| capture.extract());
-
 	let mut embeds: Vec<CreateEmbed> = vec![];
+	let mut attachments: Vec<CreateAttachment> = vec![];
 
-	for (url, [target_guild_id, target_channel_id, target_message_id]) in matches {
-		if target_guild_id != guild_id.to_string() {
-			debug!("Not resolving message from other server");
-			continue;
-		}
-		trace!("Attempting to resolve message {target_message_id} from URL {url}");
-
-		let target_channel = ChannelId::from_str(target_channel_id)?
-			.to_channel(ctx)
-			.await?
-			.guild()
-			.ok_or_else(|| {
-				eyre!("Couldn't find GuildChannel from ChannelId {target_channel_id}!")
-			})?;
-
-		if !member_can_view_channel(ctx, &author, &target_channel).await? {
-			debug!("Not resolving message for author who can't see it");
-			continue;
-		}
-
-		let target_message_id = MessageId::from_str(target_message_id)?;
-		let target_message = target_channel
-			.message(ctx, target_message_id)
-			.await
-			.wrap_err_with(|| {
-				eyre!("Couldn't find channel message from ID {target_message_id}!")
-			})?;
-
-		let embed = to_embed(ctx, &target_message).await?;
+	for target in parse_targets(&msg.content) {
+		let resolved = match target {
+			LinkTarget::Message {
+				guild_id: target_guild_id,
+				channel_id,
+				message_id,
+			} => {
+				resolve_message_link(
+					ctx,
+					http,
+					&author,
+					guild_id,
+					target_guild_id,
+					channel_id,
+					message_id,
+					config,
+					policy,
+				)
+				.await?
+			}
+			LinkTarget::Channel {
+				guild_id: target_guild_id,
+				channel_id,
+			} => {
+				resolve_channel_link(
+					ctx,
+					&author,
+					guild_id,
+					target_guild_id,
+					channel_id,
+					policy,
+				)
+				.await?
+			}
+			LinkTarget::User { user_id } => resolve_user_link(ctx, guild_id, user_id).await?,
+		};
 
-		embeds.push(embed);
+		embeds.extend(resolved.embeds);
+		attachments.extend(resolved.attachments);
 	}
 
-	Ok(embeds)
+	Ok((embeds, attachments))
 }